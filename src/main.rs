@@ -6,6 +6,8 @@
 
 use raylib::prelude::*;
 use std::f32::consts::PI;
+use std::fs::File;
+use std::io::BufWriter;
 
 #[derive(Clone, Copy)]
 struct Complex {
@@ -46,6 +48,10 @@ impl Complex {
         }
     }
 
+    fn phase(&self) -> f32 {
+        self.im.atan2(self.re)
+    }
+
     fn multiply(&self, other: &Complex) -> Complex {
         Complex {
             re: self.re * other.re - self.im * other.im,
@@ -63,7 +69,37 @@ struct PathFourier {
     components: Vec<FourierComponent>,
     center: Vector2,
     color: Color,
-    path: Vec<Vector2>,
+    path: Vec<(Vector2, f32)>,
+    source: Vec<Vector2>,
+}
+
+/// Map a complex phase angle (`-π..π`) to an RGB color via HSV, with the hue
+/// encoding direction and brightness optionally modulated by magnitude.
+fn phase_color(phase: f32, magnitude: f32) -> Color {
+    let hue = (phase + PI) / (2.0 * PI) * 360.0;
+    let value = (magnitude / 50.0).clamp(0.3, 1.0);
+    hsv_to_rgb(hue, 1.0, value)
+}
+
+fn hsv_to_rgb(h: f32, s: f32, v: f32) -> Color {
+    let c = v * s;
+    let h = (h % 360.0) / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as i32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = v - c;
+    Color::new(
+        ((r + m) * 255.0) as u8,
+        ((g + m) * 255.0) as u8,
+        ((b + m) * 255.0) as u8,
+        255,
+    )
 }
 
 impl PathFourier {
@@ -74,6 +110,7 @@ impl PathFourier {
                 center,
                 color,
                 path: vec![],
+                source: vec![],
             };
         }
 
@@ -92,9 +129,14 @@ impl PathFourier {
             center,
             color,
             path: vec![],
+            source: path_points.to_vec(),
         }
     }
 
+    fn rebuild(&mut self, num_components: usize) {
+        *self = Self::new(&self.source, self.center, self.color, num_components);
+    }
+
     fn evaluate(&self, time: f32) -> Vector2 {
         let mut sum = Complex::new(0.0, 0.0);
 
@@ -107,14 +149,14 @@ impl PathFourier {
         Vector2::new(self.center.x + sum.re, self.center.y + sum.im)
     }
 
-    fn update_path(&mut self, point: Vector2, max_path_length: usize) {
-        self.path.insert(0, point);
+    fn update_path(&mut self, point: Vector2, phase: f32, max_path_length: usize) {
+        self.path.insert(0, (point, phase));
         if self.path.len() > max_path_length {
             self.path.pop();
         }
     }
 
-    fn draw_vectors(&self, d: &mut RaylibDrawHandle, time: f32, show_circles: bool) {
+    fn draw_vectors(&self, d: &mut RaylibDrawHandle, time: f32, show_circles: bool, color_by_phase: bool) {
         let mut current = Complex::new(0.0, 0.0);
 
         for comp in &self.components {
@@ -141,12 +183,20 @@ impl PathFourier {
                 );
             }
 
-            d.draw_line_ex(prev_pos, current_pos, 2.5, Color::WHITE);
-            d.draw_circle(current_pos.x as i32, current_pos.y as i32, 3.0, Color::WHITE);
+            // Color each arm by the phase of its rotated vector, so direction
+            // of travel reads as hue.
+            let arm_color = if color_by_phase {
+                phase_color(rot.phase(), rot.magnitude())
+            } else {
+                Color::WHITE
+            };
+
+            d.draw_line_ex(prev_pos, current_pos, 2.5, arm_color);
+            d.draw_circle(current_pos.x as i32, current_pos.y as i32, 3.0, arm_color);
         }
     }
 
-    fn draw_path(&self, d: &mut RaylibDrawHandle) {
+    fn draw_path(&self, d: &mut RaylibDrawHandle, color_by_phase: bool) {
         if self.path.len() < 2 {
             return;
         }
@@ -155,42 +205,100 @@ impl PathFourier {
         let len = self.path.len().min(2000);
         for i in 1..len {
             let alpha = ((1.0 - (i as f32 / len as f32)) * max_alpha) as u8;
-            let fade_color = Color::new(
-                self.color.r,
-                self.color.g,
-                self.color.b,
-                alpha,
-            );
-            d.draw_line_ex(self.path[i - 1], self.path[i], 2.0, fade_color);
+            // Color each segment by the tip phase recorded when it was drawn.
+            let segment_color = if color_by_phase {
+                let c = phase_color(self.path[i].1, 50.0);
+                Color::new(c.r, c.g, c.b, alpha)
+            } else {
+                Color::new(self.color.r, self.color.g, self.color.b, alpha)
+            };
+            d.draw_line_ex(self.path[i - 1].0, self.path[i].0, 2.0, segment_color);
         }
     }
 }
 
-fn compute_complex_dft(signal: &[Complex], num_components: usize) -> Vec<FourierComponent> {
+fn fft(signal: &[Complex]) -> Vec<Complex> {
+    // Caller is responsible for making `signal.len()` a power of two; padding
+    // it with zeros here would treat the (periodic, closed) path as a
+    // one-shot transient and introduce spectral leakage.
     let n = signal.len();
-    if n == 0 {
-        return vec![];
+    let mut a = signal.to_vec();
+
+    if n <= 1 {
+        return a;
     }
 
-    let mut components = Vec::new();
-    
-    for k in 0..n {
-        let mut sum = Complex::new(0.0, 0.0);
+    let bits = n.trailing_zeros();
 
-        for (i, &value) in signal.iter().enumerate() {
-            let angle = -2.0 * PI * k as f32 * i as f32 / n as f32;
-            let exp_term = Complex::new(angle.cos(), angle.sin());
-            let product = exp_term.multiply(&value);
-            sum = sum.add(&product);
+    // Bit-reversal permutation: send index i to the reversal of its log2(n) bits.
+    for i in 0..n {
+        let j = (i as u32).reverse_bits() >> (32 - bits);
+        let j = j as usize;
+        if j > i {
+            a.swap(i, j);
+        }
+    }
+
+    // log2(n) butterfly stages, doubling the group size each stage.
+    let mut m = 2;
+    while m <= n {
+        let half = m / 2;
+        let mut k = 0;
+        while k < n {
+            for j in 0..half {
+                let angle = -2.0 * PI * j as f32 / m as f32;
+                let w = Complex::new(angle.cos(), angle.sin());
+                let t = w.multiply(&a[k + j + half]);
+                let u = a[k + j];
+                a[k + j] = u.add(&t);
+                a[k + j + half] = u.add(&t.scale(-1.0));
+            }
+            k += m;
         }
+        m *= 2;
+    }
+
+    a
+}
+
+// Resample a closed, periodic signal up to `target_len` points (a power of
+// two) by arc length. Unlike zero-padding, this preserves the curve's
+// implicit periodicity instead of treating it as a transient that decays to
+// zero, so the reconstructed epicycle position stays close to what the
+// original (unpadded) DFT would have produced.
+fn resample_closed_to_pow2(signal: &[Complex], target_len: usize) -> Vec<Complex> {
+    if signal.len() == target_len {
+        return signal.to_vec();
+    }
+
+    let mut closed: Vec<Vector2> = signal.iter().map(|c| Vector2::new(c.re, c.im)).collect();
+    closed.push(closed[0]);
+
+    resample_by_arc_length(&closed, target_len)
+        .into_iter()
+        .map(|p| Complex::new(p.x, p.y))
+        .collect()
+}
+
+fn compute_complex_dft(signal: &[Complex], num_components: usize) -> Vec<FourierComponent> {
+    if signal.is_empty() {
+        return vec![];
+    }
+
+    let resampled = resample_closed_to_pow2(signal, signal.len().next_power_of_two());
+    let spectrum = fft(&resampled);
+    let n = spectrum.len();
+
+    let mut components = Vec::new();
 
-        let coef = sum.scale(1.0 / n as f32);
+    for (k, &value) in spectrum.iter().enumerate() {
+        let coef = value.scale(1.0 / n as f32);
         let freq = if k <= n / 2 {
             k as i32
         } else {
             k as i32 - n as i32
         };
-        
+
         components.push(FourierComponent { freq, coef });
     }
 
@@ -203,6 +311,592 @@ fn compute_complex_dft(signal: &[Complex], num_components: usize) -> Vec<Fourier
     components
 }
 
+fn svg_path_to_points(d: &str, samples: usize) -> Vec<Vector2> {
+    // Flatten an SVG `d` attribute into a flat polyline, then resample it by
+    // arc length so the DFT receives evenly-spaced points.
+    let tokens = tokenize_svg_path(d);
+    let mut i = 0;
+
+    let mut polyline: Vec<Vector2> = Vec::new();
+    let mut current = Vector2::new(0.0, 0.0);
+    let mut start = Vector2::new(0.0, 0.0);
+    // Reflection control point of the previous cubic/quadratic, for S/T.
+    let mut last_cubic_ctrl: Option<Vector2> = None;
+    let mut last_quad_ctrl: Option<Vector2> = None;
+
+    let mut cmd = ' ';
+    while i < tokens.len() {
+        // A leading number means "repeat the previous command".
+        if let Token::Command(c) = tokens[i] {
+            cmd = c;
+            i += 1;
+        }
+
+        let abs = cmd.is_ascii_uppercase();
+        let num = |i: &mut usize| -> f32 {
+            let v = match tokens.get(*i) {
+                Some(Token::Number(n)) => *n,
+                _ => 0.0,
+            };
+            *i += 1;
+            v
+        };
+
+        match cmd.to_ascii_uppercase() {
+            'M' => {
+                let mut p = Vector2::new(num(&mut i), num(&mut i));
+                if !abs {
+                    p = Vector2::new(current.x + p.x, current.y + p.y);
+                }
+                current = p;
+                start = p;
+                polyline.push(p);
+                // Subsequent coordinate pairs after an M are treated as L.
+                cmd = if abs { 'L' } else { 'l' };
+                last_cubic_ctrl = None;
+                last_quad_ctrl = None;
+            }
+            'L' => {
+                let mut p = Vector2::new(num(&mut i), num(&mut i));
+                if !abs {
+                    p = Vector2::new(current.x + p.x, current.y + p.y);
+                }
+                current = p;
+                polyline.push(p);
+                last_cubic_ctrl = None;
+                last_quad_ctrl = None;
+            }
+            'H' => {
+                let mut x = num(&mut i);
+                if !abs {
+                    x += current.x;
+                }
+                current = Vector2::new(x, current.y);
+                polyline.push(current);
+                last_cubic_ctrl = None;
+                last_quad_ctrl = None;
+            }
+            'V' => {
+                let mut y = num(&mut i);
+                if !abs {
+                    y += current.y;
+                }
+                current = Vector2::new(current.x, y);
+                polyline.push(current);
+                last_cubic_ctrl = None;
+                last_quad_ctrl = None;
+            }
+            'C' | 'S' => {
+                let c1 = if cmd.to_ascii_uppercase() == 'S' {
+                    // Reflect the previous cubic control point across current.
+                    match last_cubic_ctrl {
+                        Some(prev) => Vector2::new(2.0 * current.x - prev.x, 2.0 * current.y - prev.y),
+                        None => current,
+                    }
+                } else {
+                    let mut p = Vector2::new(num(&mut i), num(&mut i));
+                    if !abs {
+                        p = Vector2::new(current.x + p.x, current.y + p.y);
+                    }
+                    p
+                };
+                let mut c2 = Vector2::new(num(&mut i), num(&mut i));
+                let mut end = Vector2::new(num(&mut i), num(&mut i));
+                if !abs {
+                    c2 = Vector2::new(current.x + c2.x, current.y + c2.y);
+                    end = Vector2::new(current.x + end.x, current.y + end.y);
+                }
+                flatten_cubic(current, c1, c2, end, &mut polyline);
+                current = end;
+                last_cubic_ctrl = Some(c2);
+                last_quad_ctrl = None;
+            }
+            'Q' | 'T' => {
+                let c = if cmd.to_ascii_uppercase() == 'T' {
+                    match last_quad_ctrl {
+                        Some(prev) => Vector2::new(2.0 * current.x - prev.x, 2.0 * current.y - prev.y),
+                        None => current,
+                    }
+                } else {
+                    let mut p = Vector2::new(num(&mut i), num(&mut i));
+                    if !abs {
+                        p = Vector2::new(current.x + p.x, current.y + p.y);
+                    }
+                    p
+                };
+                let mut end = Vector2::new(num(&mut i), num(&mut i));
+                if !abs {
+                    end = Vector2::new(current.x + end.x, current.y + end.y);
+                }
+                flatten_quadratic(current, c, end, &mut polyline);
+                current = end;
+                last_quad_ctrl = Some(c);
+                last_cubic_ctrl = None;
+            }
+            'A' => {
+                let rx = num(&mut i);
+                let ry = num(&mut i);
+                let x_axis_rot = num(&mut i);
+                let large_arc = num(&mut i) != 0.0;
+                let sweep = num(&mut i) != 0.0;
+                let mut end = Vector2::new(num(&mut i), num(&mut i));
+                if !abs {
+                    end = Vector2::new(current.x + end.x, current.y + end.y);
+                }
+                flatten_arc(current, rx, ry, x_axis_rot, large_arc, sweep, end, &mut polyline);
+                current = end;
+                last_cubic_ctrl = None;
+                last_quad_ctrl = None;
+            }
+            'Z' => {
+                polyline.push(start);
+                current = start;
+                last_cubic_ctrl = None;
+                last_quad_ctrl = None;
+            }
+            _ => {
+                // Unknown command: skip the stray token to avoid an infinite loop.
+                i += 1;
+            }
+        }
+    }
+
+    // Drop any non-finite points (e.g. from a degenerate arc) before they can
+    // flow into the DFT's magnitude sort and panic on `partial_cmp().unwrap()`.
+    polyline.retain(|p| p.x.is_finite() && p.y.is_finite());
+
+    resample_by_arc_length(&polyline, samples)
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Command(char),
+    Number(f32),
+}
+
+fn tokenize_svg_path(d: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let bytes = d.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i] as char;
+        if c.is_ascii_alphabetic() {
+            tokens.push(Token::Command(c));
+            i += 1;
+        } else if c == ',' || c.is_whitespace() {
+            i += 1;
+        } else if c == '-' || c == '+' || c == '.' || c.is_ascii_digit() {
+            let start = i;
+            // Sign is only allowed at the very start of a number.
+            if c == '-' || c == '+' {
+                i += 1;
+            }
+            let mut seen_dot = false;
+            let mut seen_exp = false;
+            while i < bytes.len() {
+                let ch = bytes[i] as char;
+                if ch.is_ascii_digit() {
+                    i += 1;
+                } else if ch == '.' && !seen_dot && !seen_exp {
+                    seen_dot = true;
+                    i += 1;
+                } else if (ch == 'e' || ch == 'E') && !seen_exp {
+                    seen_exp = true;
+                    i += 1;
+                    if i < bytes.len() && (bytes[i] as char == '-' || bytes[i] as char == '+') {
+                        i += 1;
+                    }
+                } else {
+                    break;
+                }
+            }
+            if let Ok(n) = d[start..i].parse::<f32>() {
+                tokens.push(Token::Number(n));
+            }
+        } else {
+            i += 1;
+        }
+    }
+
+    tokens
+}
+
+fn flatten_cubic(p0: Vector2, p1: Vector2, p2: Vector2, p3: Vector2, out: &mut Vec<Vector2>) {
+    // Subdivide while the two control points' combined distance from the
+    // chord p0..p3 exceeds the flatness tolerance.
+    let d1 = point_line_distance(p1, p0, p3);
+    let d2 = point_line_distance(p2, p0, p3);
+    if d1 + d2 <= 0.1 {
+        out.push(p3);
+        return;
+    }
+
+    let mid = |a: Vector2, b: Vector2| Vector2::new((a.x + b.x) * 0.5, (a.y + b.y) * 0.5);
+    let p01 = mid(p0, p1);
+    let p12 = mid(p1, p2);
+    let p23 = mid(p2, p3);
+    let p012 = mid(p01, p12);
+    let p123 = mid(p12, p23);
+    let p0123 = mid(p012, p123);
+
+    flatten_cubic(p0, p01, p012, p0123, out);
+    flatten_cubic(p0123, p123, p23, p3, out);
+}
+
+fn flatten_quadratic(p0: Vector2, p1: Vector2, p2: Vector2, out: &mut Vec<Vector2>) {
+    let d = point_line_distance(p1, p0, p2);
+    if d <= 0.1 {
+        out.push(p2);
+        return;
+    }
+
+    let mid = |a: Vector2, b: Vector2| Vector2::new((a.x + b.x) * 0.5, (a.y + b.y) * 0.5);
+    let p01 = mid(p0, p1);
+    let p12 = mid(p1, p2);
+    let p012 = mid(p01, p12);
+
+    flatten_quadratic(p0, p01, p012, out);
+    flatten_quadratic(p012, p12, p2, out);
+}
+
+fn point_line_distance(p: Vector2, a: Vector2, b: Vector2) -> f32 {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    let len = (dx * dx + dy * dy).sqrt();
+    if len < 1e-6 {
+        let ex = p.x - a.x;
+        let ey = p.y - a.y;
+        return (ex * ex + ey * ey).sqrt();
+    }
+    ((p.x - a.x) * dy - (p.y - a.y) * dx).abs() / len
+}
+
+#[allow(clippy::too_many_arguments)]
+fn flatten_arc(
+    from: Vector2,
+    mut rx: f32,
+    mut ry: f32,
+    x_axis_rot_deg: f32,
+    large_arc: bool,
+    sweep: bool,
+    to: Vector2,
+    out: &mut Vec<Vector2>,
+) {
+    // Endpoint parameterization per the SVG spec (implementation notes F.6).
+    // Treat non-finite or near-zero radii as degenerate too, not just an
+    // exact 0.0 match, since dividing by a tiny-but-nonzero radius still
+    // drives cxp/cyp toward infinity and eventually NaN.
+    const MIN_RADIUS: f32 = 1e-4;
+    if !rx.is_finite() || !ry.is_finite() || rx.abs() < MIN_RADIUS || ry.abs() < MIN_RADIUS {
+        out.push(to);
+        return;
+    }
+    rx = rx.abs();
+    ry = ry.abs();
+
+    let phi = x_axis_rot_deg * PI / 180.0;
+    let cos_phi = phi.cos();
+    let sin_phi = phi.sin();
+
+    let dx = (from.x - to.x) / 2.0;
+    let dy = (from.y - to.y) / 2.0;
+    let x1p = cos_phi * dx + sin_phi * dy;
+    let y1p = -sin_phi * dx + cos_phi * dy;
+
+    // Correct out-of-range radii.
+    let lambda = (x1p * x1p) / (rx * rx) + (y1p * y1p) / (ry * ry);
+    if lambda > 1.0 {
+        let s = lambda.sqrt();
+        rx *= s;
+        ry *= s;
+    }
+
+    let num = (rx * rx * ry * ry - rx * rx * y1p * y1p - ry * ry * x1p * x1p).max(0.0);
+    let den = rx * rx * y1p * y1p + ry * ry * x1p * x1p;
+    let mut coef = if den > 0.0 { (num / den).sqrt() } else { 0.0 };
+    if large_arc == sweep {
+        coef = -coef;
+    }
+    let cxp = coef * rx * y1p / ry;
+    let cyp = -coef * ry * x1p / rx;
+
+    let cx = cos_phi * cxp - sin_phi * cyp + (from.x + to.x) / 2.0;
+    let cy = sin_phi * cxp + cos_phi * cyp + (from.y + to.y) / 2.0;
+
+    let angle = |ux: f32, uy: f32, vx: f32, vy: f32| -> f32 {
+        let dot = ux * vx + uy * vy;
+        let len = ((ux * ux + uy * uy) * (vx * vx + vy * vy)).sqrt();
+        let mut a = (dot / len).clamp(-1.0, 1.0).acos();
+        if ux * vy - uy * vx < 0.0 {
+            a = -a;
+        }
+        a
+    };
+
+    let theta1 = angle(1.0, 0.0, (x1p - cxp) / rx, (y1p - cyp) / ry);
+    let mut delta = angle(
+        (x1p - cxp) / rx,
+        (y1p - cyp) / ry,
+        (-x1p - cxp) / rx,
+        (-y1p - cyp) / ry,
+    );
+    if !sweep && delta > 0.0 {
+        delta -= 2.0 * PI;
+    } else if sweep && delta < 0.0 {
+        delta += 2.0 * PI;
+    }
+
+    // One small segment per ~3 degrees of sweep.
+    let segments = ((delta.abs() / (PI / 60.0)).ceil() as usize).max(1);
+    for s in 1..=segments {
+        let t = theta1 + delta * (s as f32 / segments as f32);
+        let x = cx + rx * t.cos() * cos_phi - ry * t.sin() * sin_phi;
+        let y = cy + rx * t.cos() * sin_phi + ry * t.sin() * cos_phi;
+        out.push(Vector2::new(x, y));
+    }
+}
+
+fn resample_by_arc_length(polyline: &[Vector2], samples: usize) -> Vec<Vector2> {
+    if polyline.len() < 2 || samples == 0 {
+        return polyline.to_vec();
+    }
+
+    // Cumulative arc length at each vertex.
+    let mut cumulative = Vec::with_capacity(polyline.len());
+    cumulative.push(0.0f32);
+    for w in polyline.windows(2) {
+        let dx = w[1].x - w[0].x;
+        let dy = w[1].y - w[0].y;
+        let prev = *cumulative.last().unwrap();
+        cumulative.push(prev + (dx * dx + dy * dy).sqrt());
+    }
+    let total = *cumulative.last().unwrap();
+    if total < 1e-6 {
+        return vec![polyline[0]; samples];
+    }
+
+    let mut result = Vec::with_capacity(samples);
+    let mut seg = 0;
+    for i in 0..samples {
+        let target = total * i as f32 / samples as f32;
+        while seg + 1 < cumulative.len() - 1 && cumulative[seg + 1] < target {
+            seg += 1;
+        }
+        let seg_start = cumulative[seg];
+        let seg_end = cumulative[seg + 1];
+        let frac = if seg_end - seg_start > 1e-6 {
+            (target - seg_start) / (seg_end - seg_start)
+        } else {
+            0.0
+        };
+        let a = polyline[seg];
+        let b = polyline[seg + 1];
+        result.push(Vector2::new(a.x + (b.x - a.x) * frac, a.y + (b.y - a.y) * frac));
+    }
+
+    result
+}
+
+/// Simple RGBA software framebuffer used by the offline APNG exporter.
+struct Framebuffer {
+    width: i32,
+    height: i32,
+    pixels: Vec<u8>,
+}
+
+impl Framebuffer {
+    fn new(width: i32, height: i32) -> Self {
+        // Opaque black background, matching the live window's clear color.
+        let mut pixels = vec![0u8; (width * height * 4) as usize];
+        for px in pixels.chunks_exact_mut(4) {
+            px[3] = 255;
+        }
+        Framebuffer {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    fn clear(&mut self) {
+        for px in self.pixels.chunks_exact_mut(4) {
+            px[0] = 0;
+            px[1] = 0;
+            px[2] = 0;
+            px[3] = 255;
+        }
+    }
+
+    fn blend(&mut self, x: i32, y: i32, color: Color) {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return;
+        }
+        let idx = ((y * self.width + x) * 4) as usize;
+        let a = color.a as f32 / 255.0;
+        for (c, src) in [color.r, color.g, color.b].iter().enumerate() {
+            let dst = self.pixels[idx + c] as f32;
+            self.pixels[idx + c] = (dst * (1.0 - a) + *src as f32 * a) as u8;
+        }
+    }
+
+    fn dot(&mut self, cx: f32, cy: f32, radius: f32, color: Color) {
+        let r = radius.ceil() as i32;
+        let cxi = cx.round() as i32;
+        let cyi = cy.round() as i32;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                if (dx * dx + dy * dy) as f32 <= radius * radius {
+                    self.blend(cxi + dx, cyi + dy, color);
+                }
+            }
+        }
+    }
+
+    fn line(&mut self, a: Vector2, b: Vector2, thickness: f32, color: Color) {
+        // Bresenham with a small disc stamped at each step for thickness.
+        let mut x0 = a.x.round() as i32;
+        let mut y0 = a.y.round() as i32;
+        let x1 = b.x.round() as i32;
+        let y1 = b.y.round() as i32;
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+        let radius = (thickness / 2.0).max(0.5);
+        loop {
+            self.dot(x0 as f32, y0 as f32, radius, color);
+            if x0 == x1 && y0 == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x0 += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y0 += sy;
+            }
+        }
+    }
+
+    fn circle_lines(&mut self, cx: f32, cy: f32, radius: f32, color: Color) {
+        let steps = ((radius * 2.0 * PI).ceil() as usize).max(8);
+        let mut prev = Vector2::new(cx + radius, cy);
+        for i in 1..=steps {
+            let a = 2.0 * PI * i as f32 / steps as f32;
+            let p = Vector2::new(cx + radius * a.cos(), cy + radius * a.sin());
+            self.line(prev, p, 1.0, color);
+            prev = p;
+        }
+    }
+}
+
+/// Render the full `time: 0.0..1.0` sweep to an animated PNG on disk. Because the
+/// motion is exactly periodic over one unit of `time`, the file loops seamlessly.
+fn export_apng(
+    paths: &[PathFourier],
+    frames: usize,
+    size: (i32, i32),
+    path: &str,
+    color_by_phase: bool,
+) {
+    let (width, height) = size;
+
+    // Local mutable copies so we can accumulate each path's trail across frames
+    // without disturbing the caller's state.
+    let mut sim: Vec<PathFourier> = paths
+        .iter()
+        .map(|p| PathFourier::new(&p.source, p.center, p.color, p.components.len()))
+        .collect();
+
+    let file = File::create(path).expect("failed to create APNG file");
+    let writer = BufWriter::new(file);
+    let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .set_animated(frames as u32, 0)
+        .expect("failed to configure APNG");
+    // Fixed per-frame delay so one sweep plays back at a steady rate.
+    encoder.set_frame_delay(1, 30).expect("failed to set delay");
+    let mut png_writer = encoder.write_header().expect("failed to write PNG header");
+
+    let mut fb = Framebuffer::new(width, height);
+
+    // Pre-roll one full sweep so every trail already spans a whole loop at
+    // frame 0; capping trails at `frames` points then keeps them identical
+    // from one loop to the next.
+    for f in 0..frames {
+        let time = f as f32 / frames as f32;
+        for p in &mut sim {
+            let point = p.evaluate(time);
+            let tip_phase = Complex::new(point.x - p.center.x, point.y - p.center.y).phase();
+            p.update_path(point, tip_phase, frames);
+        }
+    }
+
+    for f in 0..frames {
+        let time = f as f32 / frames as f32;
+        fb.clear();
+
+        for p in &mut sim {
+            // Epicycle arms and circles (mirrors draw_vectors).
+            let mut current = Complex::new(0.0, 0.0);
+            for comp in &p.components {
+                let prev = current;
+                let angle = 2.0 * PI * comp.freq as f32 * time;
+                let rot = comp.coef.rotate(angle);
+                current = current.add(&rot);
+
+                let prev_pos = Vector2::new(p.center.x + prev.re, p.center.y + prev.im);
+                let current_pos = Vector2::new(p.center.x + current.re, p.center.y + current.im);
+
+                if comp.coef.magnitude() > 0.1 {
+                    fb.circle_lines(
+                        prev_pos.x,
+                        prev_pos.y,
+                        comp.coef.magnitude(),
+                        Color::new(150, 150, 150, 180),
+                    );
+                }
+                let arm_color = if color_by_phase {
+                    phase_color(rot.phase(), rot.magnitude())
+                } else {
+                    Color::WHITE
+                };
+                fb.line(prev_pos, current_pos, 2.5, arm_color);
+                fb.dot(current_pos.x, current_pos.y, 3.0, arm_color);
+            }
+
+            let point = p.evaluate(time);
+            let tip_phase = current.phase();
+            p.update_path(point, tip_phase, frames);
+
+            // Fading trail (mirrors draw_path).
+            let len = p.path.len().min(2000);
+            for i in 1..len {
+                let alpha = ((1.0 - (i as f32 / len as f32)) * 255.0) as u8;
+                let fade = if color_by_phase {
+                    let c = phase_color(p.path[i].1, 50.0);
+                    Color::new(c.r, c.g, c.b, alpha)
+                } else {
+                    Color::new(p.color.r, p.color.g, p.color.b, alpha)
+                };
+                fb.line(p.path[i - 1].0, p.path[i].0, 2.0, fade);
+            }
+        }
+
+        png_writer
+            .write_image_data(&fb.pixels)
+            .expect("failed to write APNG frame");
+    }
+
+    png_writer.finish().expect("failed to finalize APNG");
+}
+
 fn create_square_path(size: f32) -> Vec<Vector2> {
     let half = size / 2.0;
     let mut points = Vec::new();
@@ -259,6 +953,17 @@ fn create_heart_path(scale: f32) -> Vec<Vector2> {
     points
 }
 
+// Decimation predicate for the freehand drawing buffer: only keep a new
+// sample once it has moved far enough from the last recorded point.
+fn should_sample_point(last: Option<Vector2>, p: Vector2, min_dist: f32) -> bool {
+    last.map(|last| {
+        let dx = p.x - last.x;
+        let dy = p.y - last.y;
+        (dx * dx + dy * dy).sqrt() > min_dist
+    })
+    .unwrap_or(true)
+}
+
 fn main() {
     let (mut rl, thread) = raylib::init()
         .size(1200, 800)
@@ -267,8 +972,12 @@ fn main() {
 
     let mut time: f32 = 0.0;
     let dt: f32 = 0.0005;
-    let num_components = 100;
+    let mut num_components = 100;
     let show_circles = true;
+    let mut color_by_phase = true;
+
+    // Freehand drawing buffer, accumulated while the left mouse button is held.
+    let mut drawing: Vec<Vector2> = Vec::new();
 
     let square_path = create_square_path(150.0);
     let circle_path = create_circle_path(120.0);
@@ -287,18 +996,100 @@ fn main() {
         PathFourier::new(&heart_path2, Vector2::new(900.0, 500.0), Color::ORANGE, num_components),
     ];
 
+    // Optional CLI arg: a path to a file holding an SVG `d` attribute (or the
+    // `d` string itself), imported via svg_path_to_points so any vector
+    // glyph/logo can be fed into the epicycle renderer.
+    if let Some(svg_arg) = std::env::args().nth(1) {
+        let d = std::fs::read_to_string(&svg_arg).unwrap_or(svg_arg);
+        let svg_points = svg_path_to_points(&d, 300);
+        if svg_points.len() > 2 {
+            let cx = svg_points.iter().map(|p| p.x).sum::<f32>() / svg_points.len() as f32;
+            let cy = svg_points.iter().map(|p| p.y).sum::<f32>() / svg_points.len() as f32;
+            let centered: Vec<Vector2> = svg_points
+                .iter()
+                .map(|p| Vector2::new(p.x - cx, p.y - cy))
+                .collect();
+            paths.push(PathFourier::new(
+                &centered,
+                Vector2::new(600.0, 650.0),
+                Color::PURPLE,
+                num_components,
+            ));
+        }
+    }
+
     while !rl.window_should_close() {
+        // Freehand drawing: accumulate points while the left button is held,
+        // decimating samples that land within a few pixels of the last one.
+        if rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+            let p = rl.get_mouse_position();
+            if should_sample_point(drawing.last().copied(), p, 5.0) {
+                drawing.push(p);
+            }
+        }
+
+        if rl.is_mouse_button_released(MouseButton::MOUSE_BUTTON_LEFT) {
+            if drawing.len() > 2 {
+                let cx = drawing.iter().map(|p| p.x).sum::<f32>() / drawing.len() as f32;
+                let cy = drawing.iter().map(|p| p.y).sum::<f32>() / drawing.len() as f32;
+                let centered: Vec<Vector2> =
+                    drawing.iter().map(|p| Vector2::new(p.x - cx, p.y - cy)).collect();
+                paths.push(PathFourier::new(
+                    &centered,
+                    Vector2::new(cx, cy),
+                    Color::SKYBLUE,
+                    num_components,
+                ));
+            }
+            // Always clear on release so a stray click/release doesn't leave
+            // stale points for the next real stroke to append onto.
+            drawing.clear();
+        }
+
+        // Keyboard controls: clear everything, or tune reconstruction fidelity.
+        if rl.is_key_pressed(KeyboardKey::KEY_C) {
+            paths.clear();
+            drawing.clear();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_E) {
+            // Render one seamless loop to disk alongside the live window.
+            export_apng(&paths, 300, (1200, 800), "fourier.png", color_by_phase);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_P) {
+            color_by_phase = !color_by_phase;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+            num_components += 10;
+            for path in &mut paths {
+                path.rebuild(num_components);
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) && num_components > 10 {
+            num_components -= 10;
+            for path in &mut paths {
+                path.rebuild(num_components);
+            }
+        }
+
         let mut d = rl.begin_drawing(&thread);
         d.clear_background(Color::BLACK);
 
+        // Preview the stroke in progress.
+        if drawing.len() >= 2 {
+            for w in drawing.windows(2) {
+                d.draw_line_ex(w[0], w[1], 2.0, Color::SKYBLUE);
+            }
+        }
+
         for path in &mut paths {
             if show_circles {
-                path.draw_vectors(&mut d, time, true);
+                path.draw_vectors(&mut d, time, true, color_by_phase);
             }
 
             let point = path.evaluate(time);
-            path.update_path(point, 2000);
-            path.draw_path(&mut d);
+            let tip_phase = Complex::new(point.x - path.center.x, point.y - path.center.y).phase();
+            path.update_path(point, tip_phase, 2000);
+            path.draw_path(&mut d, color_by_phase);
         }
 
         time += dt;
@@ -307,3 +1098,153 @@ fn main() {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // O(n^2) reference DFT to check the radix-2 FFT against.
+    fn naive_dft(signal: &[Complex]) -> Vec<Complex> {
+        let n = signal.len();
+        let mut out = Vec::with_capacity(n);
+        for k in 0..n {
+            let mut sum = Complex::new(0.0, 0.0);
+            for (t, &x) in signal.iter().enumerate() {
+                let angle = -2.0 * PI * k as f32 * t as f32 / n as f32;
+                sum = sum.add(&x.multiply(&Complex::new(angle.cos(), angle.sin())));
+            }
+            out.push(sum);
+        }
+        out
+    }
+
+    #[test]
+    fn fft_matches_naive_dft_for_power_of_two_input() {
+        let signal: Vec<Complex> = (0..8)
+            .map(|i| Complex::new(i as f32, (i as f32 * 0.5).sin()))
+            .collect();
+
+        let expected = naive_dft(&signal);
+        let actual = fft(&signal);
+
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a.re - e.re).abs() < 1e-3, "re: {} vs {}", a.re, e.re);
+            assert!((a.im - e.im).abs() < 1e-3, "im: {} vs {}", a.im, e.im);
+        }
+    }
+
+    #[test]
+    fn fft_of_single_sample_is_identity() {
+        let signal = vec![Complex::new(3.0, -2.0)];
+        let result = fft(&signal);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].re, 3.0);
+        assert_eq!(result[0].im, -2.0);
+    }
+
+    #[test]
+    fn resample_by_arc_length_preserves_start_and_spacing() {
+        // Total arc length is 20 (10 along each leg); samples are taken at
+        // evenly-spaced arc-length offsets starting at 0, the same
+        // half-open convention used for a periodic/closed curve (the
+        // endpoint itself is never re-sampled, matching the first point).
+        let polyline = vec![
+            Vector2::new(0.0, 0.0),
+            Vector2::new(10.0, 0.0),
+            Vector2::new(10.0, 10.0),
+        ];
+
+        let resampled = resample_by_arc_length(&polyline, 5);
+
+        assert_eq!(resampled.len(), 5);
+        assert!((resampled[0].x - 0.0).abs() < 1e-3);
+        assert!((resampled[0].y - 0.0).abs() < 1e-3);
+        // Offset 16 along the path: 10 across the first leg, 6 up the second.
+        assert!((resampled[4].x - 10.0).abs() < 1e-3);
+        assert!((resampled[4].y - 6.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn tokenize_svg_path_splits_commands_and_numbers() {
+        let tokens = tokenize_svg_path("M0,0 L10,-5.5Z");
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Command('M'),
+                Token::Number(0.0),
+                Token::Number(0.0),
+                Token::Command('L'),
+                Token::Number(10.0),
+                Token::Number(-5.5),
+                Token::Command('Z'),
+            ]
+        );
+    }
+
+    #[test]
+    fn svg_path_to_points_flattens_a_triangle() {
+        let points = svg_path_to_points("M0,0 L10,0 L10,10 Z", 12);
+
+        assert_eq!(points.len(), 12);
+        // The whole polyline should stay within the triangle's bounding box.
+        for p in &points {
+            assert!(p.x >= -1e-3 && p.x <= 10.0 + 1e-3);
+            assert!(p.y >= -1e-3 && p.y <= 10.0 + 1e-3);
+        }
+    }
+
+    #[test]
+    fn flatten_cubic_hits_the_endpoint() {
+        let mut out = Vec::new();
+        flatten_cubic(
+            Vector2::new(0.0, 0.0),
+            Vector2::new(0.0, 10.0),
+            Vector2::new(10.0, 10.0),
+            Vector2::new(10.0, 0.0),
+            &mut out,
+        );
+
+        assert!(out.len() > 1);
+        let last = out.last().unwrap();
+        assert!((last.x - 10.0).abs() < 1e-3);
+        assert!((last.y - 0.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn flatten_quadratic_hits_the_endpoint() {
+        let mut out = Vec::new();
+        flatten_quadratic(
+            Vector2::new(0.0, 0.0),
+            Vector2::new(5.0, 10.0),
+            Vector2::new(10.0, 0.0),
+            &mut out,
+        );
+
+        let last = out.last().unwrap();
+        assert!((last.x - 10.0).abs() < 1e-3);
+        assert!((last.y - 0.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn flatten_arc_falls_back_to_a_line_for_degenerate_radii() {
+        let mut out = Vec::new();
+        let from = Vector2::new(0.0, 0.0);
+        let to = Vector2::new(10.0, 10.0);
+
+        flatten_arc(from, 1e-8, 1e-8, 0.0, false, true, to, &mut out);
+
+        assert_eq!(out.len(), 1);
+        assert!(out[0].x.is_finite() && out[0].y.is_finite());
+        assert_eq!(out[0].x, to.x);
+        assert_eq!(out[0].y, to.y);
+    }
+
+    #[test]
+    fn should_sample_point_decimates_nearby_points() {
+        let last = Some(Vector2::new(0.0, 0.0));
+        assert!(!should_sample_point(last, Vector2::new(1.0, 0.0), 5.0));
+        assert!(should_sample_point(last, Vector2::new(6.0, 0.0), 5.0));
+        assert!(should_sample_point(None, Vector2::new(0.0, 0.0), 5.0));
+    }
+}